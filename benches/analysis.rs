@@ -0,0 +1,66 @@
+#![allow(non_snake_case)]
+#![feature(test)]
+
+extern crate matrix;
+extern crate temperature;
+extern crate test;
+
+use temperature::{Analysis, Backend, Circuit, Config, Leakage, Propagation};
+use test::{black_box, Bencher};
+
+const CORES: usize = 16;
+const NODES: usize = 1024;
+
+// A synthetic chain in which each node couples to its neighbors, producing a
+// symmetric tridiagonal conductance large enough to expose the per-step kernels.
+fn circuit() -> Circuit {
+    let mut values = vec![0.0; NODES * NODES];
+    for i in 0..NODES {
+        values[i * NODES + i] = 2.0;
+        if i + 1 < NODES {
+            values[(i + 1) * NODES + i] = -1.0;
+            values[i * NODES + (i + 1)] = -1.0;
+        }
+    }
+    Circuit {
+        cores: CORES,
+        nodes: NODES,
+        capacitance: vec![1.0; NODES],
+        conductance: matrix::Dense { rows: NODES, columns: NODES, values: values },
+    }
+}
+
+fn config(propagation: Propagation) -> Config {
+    Config {
+        ambience: 318.15,
+        time_step: 1e-3,
+        propagation: propagation,
+        backend: Backend::Eigen,
+        leakage: Leakage::None,
+        tolerance: 1e-12,
+        max_iterations: 20,
+    }
+}
+
+#[bench]
+fn setup(bencher: &mut Bencher) {
+    let circuit = circuit();
+    let config = config(Propagation::Dense);
+    bencher.iter(|| black_box(Analysis::new(&circuit, &config).unwrap()));
+}
+
+#[bench]
+fn step_dense(bencher: &mut Bencher) {
+    let mut analysis = Analysis::new(&circuit(), &config(Propagation::Dense)).unwrap();
+    let P = vec![1.0; CORES];
+    let mut Q = vec![0.0; CORES];
+    bencher.iter(|| analysis.step(black_box(&P), &mut Q));
+}
+
+#[bench]
+fn step_modal(bencher: &mut Bencher) {
+    let mut analysis = Analysis::new(&circuit(), &config(Propagation::Modal)).unwrap();
+    let P = vec![1.0; CORES];
+    let mut Q = vec![0.0; CORES];
+    bencher.iter(|| analysis.step(black_box(&P), &mut Q));
+}
@@ -9,6 +9,56 @@ use {Circuit, Config, Error, Result};
 #[cfg(test)]
 mod tests;
 
+/// Coefficients of the diagonal order-6 Padé approximant of the exponential.
+const PADE: [f64; 7] = [
+    1.0,
+    1.0 / 2.0,
+    5.0 / 44.0,
+    1.0 / 66.0,
+    1.0 / 792.0,
+    1.0 / 15840.0,
+    1.0 / 665280.0,
+];
+
+/// A propagation strategy.
+#[derive(Clone, Copy)]
+pub enum Propagation {
+    /// Form the dense propagator `E` and advance the physical state directly.
+    Dense,
+    /// Stay in the eigenbasis and advance the modal state, avoiding `E`.
+    Modal,
+}
+
+/// A way of computing the propagator and forcing matrices.
+#[derive(Clone, Copy)]
+pub enum Backend {
+    /// Diagonalize the symmetric normalized matrix with `symmetric_eigen`.
+    Eigen,
+    /// Exponentiate via scaling and squaring, tolerating non-symmetric input.
+    Pade,
+}
+
+/// A temperature-dependent leakage-power model, evaluated per core.
+#[derive(Clone, Copy)]
+pub enum Leakage {
+    /// No leakage; the power fed to `step` is purely dynamic.
+    None,
+    /// `P_leak(T) = a + b·T`.
+    Linear { a: f64, b: f64 },
+    /// `P_leak(T) = a·exp(b·T)`.
+    Exponential { a: f64, b: f64 },
+}
+
+impl Leakage {
+    fn evaluate(&self, temperature: f64) -> f64 {
+        match *self {
+            Leakage::None => 0.0,
+            Leakage::Linear { a, b } => a + b * temperature,
+            Leakage::Exponential { a, b } => a * (b * temperature).exp(),
+        }
+    }
+}
+
 /// Temperature analysis.
 pub struct Analysis {
     config: Config,
@@ -21,11 +71,16 @@ struct System {
     nodes: usize,
 
     U: Vec<f64>,
+    Ut: Vec<f64>,
     L: Vec<f64>,
     D: Vec<f64>,
     E: Vec<f64>,
     F: Vec<f64>,
 
+    El: Vec<f64>,
+    G: Vec<f64>,
+    Z: Vec<f64>,
+
     S: Vec<f64>,
 }
 
@@ -46,6 +101,22 @@ impl Analysis {
             }
         }
 
+        let system = match config.backend {
+            Backend::Pade => Analysis::setup_pade(cores, nodes, D, A, config)?,
+            Backend::Eigen => match Analysis::setup_eigen(cores, nodes, D.clone(), A.clone(), config) {
+                Ok(system) => system,
+                // A non-symmetric or otherwise non-diagonalizable matrix makes the
+                // eigensolver fail; fall back to the scaling-and-squaring backend.
+                Err(_) => Analysis::setup_pade(cores, nodes, D, A, config)?,
+            },
+        };
+
+        Ok(Analysis { config: *config, system: system })
+    }
+
+    fn setup_eigen(cores: usize, nodes: usize, D: Vec<f64>, A: Vec<f64>, config: &Config)
+        -> Result<System> {
+
         let mut U = A; // recycle
         let mut L = vec![0.0; nodes];
         if let Err(error) = linear::symmetric_eigen(&mut U, &mut L) {
@@ -60,39 +131,174 @@ impl Analysis {
         for i in 0..nodes {
             T1[i] = (dt * L[i]).exp();
         }
-        for i in 0..nodes {
-            for j in 0..nodes {
-                T2[j * nodes + i] = T1[i] * U[i * nodes + j];
+
+        // Retain the per-mode multipliers exp(dt·L) before T1 is overwritten; the
+        // modal path advances `Z[i] = El[i]·Z[i] + (G·p)[i]`.
+        let El = T1.clone();
+
+        // Explicit transpose so the per-step reductions and the setup kernels below
+        // can read whole rows of `U` as contiguous slices (column i of `Ut` = row i
+        // of `U`) instead of striding with a bounds-checked `U[·*nodes + ·]`.
+        let mut Ut = vec![0.0; nodes * nodes];
+        for (i, urow) in U.chunks_exact(nodes).enumerate() {
+            for (utcolumn, &u) in Ut.chunks_exact_mut(nodes).zip(urow.iter()) {
+                utcolumn[i] = u;
             }
         }
 
-        let mut E = vec![0.0; nodes * nodes];
-        linear::multiply(1.0, &U, &T2, 1.0, &mut E, nodes);
+        let mut E = Vec::new();
+        if let Propagation::Dense = config.propagation {
+            for (column, utrow) in T2.chunks_exact_mut(nodes).zip(Ut.chunks_exact(nodes)) {
+                for (value, (&scale, &u)) in column.iter_mut().zip(T1.iter().zip(utrow.iter())) {
+                    *value = scale * u;
+                }
+            }
+            E = vec![0.0; nodes * nodes];
+            linear::multiply(1.0, &U, &T2, 1.0, &mut E, nodes);
+        }
 
         for i in 0..nodes {
             T1[i] = (T1[i] - 1.0) / L[i];
         }
-        for i in 0..nodes {
-            for j in 0..cores {
-                T2[j * nodes + i] = T1[i] * U[i * nodes + j] * D[j];
+        for (column, (utrow, &scale)) in T2[..(nodes * cores)]
+            .chunks_exact_mut(nodes)
+            .zip(Ut.chunks_exact(nodes).zip(D.iter()))
+        {
+            for (value, (&t1, &u)) in column.iter_mut().zip(T1.iter().zip(utrow.iter())) {
+                *value = t1 * u * scale;
             }
         }
 
         let mut F = vec![0.0; nodes * cores];
         linear::multiply(1.0, &U, &T2[..(nodes * cores)], 1.0, &mut F, nodes);
 
-        Ok(Analysis {
-            config: *config,
-            system: System {
-                cores: cores, nodes: nodes,
-                L: L, U: U, D: D, E: E, F: F,
-                S: vec![0.0; 2 * nodes],
-            },
+        let mut G = Vec::new();
+        let mut Z = Vec::new();
+        if let Propagation::Modal = config.propagation {
+            // G = Uᵀ·F (nodes×cores), the power injection expressed in the eigenbasis.
+            G = vec![0.0; nodes * cores];
+            for (gcolumn, fcolumn) in G.chunks_exact_mut(nodes).zip(F.chunks_exact(nodes)) {
+                for (i, value) in gcolumn.iter_mut().enumerate() {
+                    let eigenvector = &U[(i * nodes)..((i + 1) * nodes)];
+                    let mut sum = 0.0;
+                    for (&u, &f) in eigenvector.iter().zip(fcolumn.iter()) {
+                        sum += u * f;
+                    }
+                    *value = sum;
+                }
+            }
+            Z = vec![0.0; nodes];
+        }
+
+        Ok(System {
+            cores: cores, nodes: nodes,
+            L: L, U: U, Ut: Ut, D: D, E: E, F: F,
+            El: El, G: G, Z: Z,
+            S: vec![0.0; 2 * nodes],
+        })
+    }
+
+    fn setup_pade(cores: usize, nodes: usize, D: Vec<f64>, A: Vec<f64>, config: &Config)
+        -> Result<System> {
+
+        if let Propagation::Modal = config.propagation {
+            return Err(Error("modal propagation requires the eigenbasis backend".to_string()));
+        }
+
+        let dt = config.time_step;
+        let order = nodes + cores;
+
+        // Augmented matrix M = dt·[[A, B], [0, 0]] with B[k, j] = D[j]·δ(k, j); its
+        // exponential has E in the top-left and F = A⁻¹(E − I)B in the top-right block.
+        let mut M = vec![0.0; order * order];
+        for j in 0..nodes {
+            for i in 0..nodes {
+                M[j * order + i] = dt * A[j * nodes + i];
+            }
+        }
+        for j in 0..cores {
+            M[(nodes + j) * order + j] = dt * D[j];
+        }
+
+        // s = max(0, ceil(log2(‖M‖_inf))), then scale M down by 2^s. The norm is
+        // taken over the full augmented M (including the B block) rather than over
+        // ‖dt·A‖_inf alone; this can only pick a larger s, which is numerically
+        // harmless (extra squarings are exact for the 2^s factor).
+        let mut norm = 0.0;
+        for i in 0..order {
+            let mut row = 0.0;
+            for j in 0..order {
+                row += M[j * order + i].abs();
+            }
+            if row > norm {
+                norm = row;
+            }
+        }
+        let s = if norm > 0.0 { norm.log2().ceil().max(0.0) as i32 } else { 0 };
+        let scale = 2f64.powi(s);
+        for value in M.iter_mut() {
+            *value /= scale;
+        }
+
+        // Diagonal Padé: exp(M) ≈ D(M)⁻¹·N(M), with N = Σ c_k·M^k and D = Σ (−1)^k·c_k·M^k.
+        let mut numerator = vec![0.0; order * order];
+        let mut denominator = vec![0.0; order * order];
+        let mut power = vec![0.0; order * order];
+        for i in 0..order {
+            power[i * order + i] = 1.0;
+        }
+        let mut scratch = vec![0.0; order * order];
+        for k in 0..PADE.len() {
+            let c = PADE[k];
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            for idx in 0..(order * order) {
+                numerator[idx] += c * power[idx];
+                denominator[idx] += sign * c * power[idx];
+            }
+            if k + 1 < PADE.len() {
+                linear::multiply(1.0, &power, &M, 0.0, &mut scratch, order);
+                mem::swap(&mut power, &mut scratch);
+            }
+        }
+
+        // Solve D·R = N once, then square R a total of s times to recover exp(dt·A).
+        let mut R = numerator;
+        solve(&mut denominator, &mut R, order)?;
+        for _ in 0..s {
+            linear::multiply(1.0, &R, &R, 0.0, &mut scratch, order);
+            mem::swap(&mut R, &mut scratch);
+        }
+
+        let mut E = vec![0.0; nodes * nodes];
+        for j in 0..nodes {
+            for i in 0..nodes {
+                E[j * nodes + i] = R[j * order + i];
+            }
+        }
+        let mut F = vec![0.0; nodes * cores];
+        for j in 0..cores {
+            for i in 0..nodes {
+                F[j * nodes + i] = R[(nodes + j) * order + i];
+            }
+        }
+
+        Ok(System {
+            cores: cores, nodes: nodes,
+            L: Vec::new(), U: Vec::new(), Ut: Vec::new(), D: D, E: E, F: F,
+            El: Vec::new(), G: Vec::new(), Z: Vec::new(),
+            S: vec![0.0; 2 * nodes],
         })
     }
 
     /// Perform temperature analysis.
     pub fn step(&mut self, P: &[f64], Q: &mut [f64]) {
+        match self.config.propagation {
+            Propagation::Dense => self.step_dense(P, Q),
+            Propagation::Modal => self.step_modal(P, Q),
+        }
+    }
+
+    fn step_dense(&mut self, P: &[f64], Q: &mut [f64]) {
         let Config { ambience, .. } = self.config;
         let System { cores, nodes, ref D, ref E, ref F, ref mut S, .. } = self.system;
 
@@ -125,10 +331,217 @@ impl Analysis {
             linear::multiply(1.0, E, from, 1.0, into, nodes);
         }
 
+        for (qcolumn, scolumn) in Q.chunks_exact_mut(cores).zip(S[nodes..].chunks_exact(nodes)) {
+            for (q, (&d, &s)) in qcolumn.iter_mut().zip(D.iter().zip(scolumn.iter())) {
+                *q = d * s + ambience;
+            }
+        }
+    }
+
+    /// Perform temperature analysis with temperature-dependent leakage power.
+    pub fn step_with_leakage(&mut self, P: &[f64], Q: &mut [f64]) -> Result<()> {
+        let Config { ambience, leakage, tolerance, max_iterations, .. } = self.config;
+        let System { cores, nodes, ref D, ref E, ref F, ref mut S, .. } = self.system;
+
+        debug_assert!(P.len() % cores == 0);
+        debug_assert!(Q.len() % cores == 0);
+
+        // The fixed-point iteration reuses the dense `E`/`F` multiply, which only
+        // exists under `Propagation::Dense`.
+        if E.is_empty() {
+            return Err(Error("leakage stepping requires the dense propagator".to_string()));
+        }
+
+        let steps = P.len() / cores;
+        debug_assert!(steps > 0);
+
+        // Carry the physical state forward in the last column of S, as `step` does.
+        let current = S.len();
+        let mut x = vec![0.0; nodes];
+        x.copy_from_slice(&S[(current - nodes)..]);
+        let mut x_next = vec![0.0; nodes];
+        let mut Ex = vec![0.0; nodes];
+
+        let mut p = vec![0.0; cores];
+        let mut t = vec![0.0; cores];
         for i in 0..cores {
-            for j in 0..steps {
-                Q[j * cores + i] = D[i] * S[(j + 1) * nodes + i] + ambience;
+            t[i] = D[i] * x[i] + ambience;
+        }
+
+        for j in 0..steps {
+            let dynamic = &P[(j * cores)..((j + 1) * cores)];
+
+            // E·x is invariant across the fixed point; only the forcing F·p changes.
+            linear::multiply(1.0, E, &x, 0.0, &mut Ex, nodes);
+
+            // Fixed point: P = P_dynamic + P_leak(T), re-propagate, repeat until T settles.
+            let mut iterations = 0;
+            loop {
+                for i in 0..cores {
+                    p[i] = dynamic[i] + leakage.evaluate(t[i]);
+                }
+                x_next.copy_from_slice(&Ex);
+                linear::multiply(1.0, F, &p, 1.0, &mut x_next, nodes);
+
+                let mut change: f64 = 0.0;
+                for i in 0..cores {
+                    let temperature = D[i] * x_next[i] + ambience;
+                    change = change.max((temperature - t[i]).abs());
+                    t[i] = temperature;
+                }
+
+                iterations += 1;
+                if change < tolerance || iterations >= max_iterations {
+                    break;
+                }
+            }
+
+            mem::swap(&mut x, &mut x_next);
+            for i in 0..cores {
+                Q[j * cores + i] = t[i];
             }
         }
+
+        S[(current - nodes)..].copy_from_slice(&x);
+
+        Ok(())
     }
+
+    /// Compute the equilibrium temperature under a constant power vector.
+    ///
+    /// Unlike `step`, this is a single-column operation: `P` holds exactly one
+    /// power value per core and `Q` receives exactly one temperature per core.
+    pub fn steady_state(&self, P: &[f64], Q: &mut [f64]) -> Result<()> {
+        let Config { ambience, tolerance, .. } = self.config;
+        let System { cores, nodes, ref U, ref Ut, ref L, ref D, .. } = self.system;
+
+        debug_assert!(P.len() == cores);
+        debug_assert!(Q.len() == cores);
+
+        if L.is_empty() {
+            return Err(Error("steady-state analysis requires the eigenbasis backend".to_string()));
+        }
+
+        // B·p with (B·p)[k] = D[k]·p[k] on the core nodes; the sign of the
+        // right-hand side −B·p is applied when the temperatures are emitted.
+        let mut b = vec![0.0; nodes];
+        for (bi, (&d, &p)) in b.iter_mut().zip(D.iter().zip(P.iter())) {
+            *bi = d * p;
+        }
+
+        // Project onto the eigenbasis: c = Uᵀ·b (column i of U dotted with b).
+        let mut c = vec![0.0; nodes];
+        for (ci, urow) in c.iter_mut().zip(U.chunks_exact(nodes)) {
+            let mut sum = 0.0;
+            for (&u, &bk) in urow.iter().zip(b.iter()) {
+                sum += u * bk;
+            }
+            *ci = sum;
+        }
+
+        // Divide each mode by its (strictly negative) eigenvalue.
+        for (ci, &l) in c.iter_mut().zip(L.iter()) {
+            if l.abs() < tolerance {
+                return Err(Error("the system is singular to the given tolerance".to_string()));
+            }
+            *ci /= l;
+        }
+
+        // Project back x = −U·(c/L) and emit the core temperatures; column i of
+        // Ut is row i of U.
+        for (q, (&d, utrow)) in Q.iter_mut().zip(D.iter().zip(Ut.chunks_exact(nodes))) {
+            let mut x = 0.0;
+            for (&u, &ck) in utrow.iter().zip(c.iter()) {
+                x += u * ck;
+            }
+            *q = -1.0 * d * x + ambience;
+        }
+
+        Ok(())
+    }
+
+    fn step_modal(&mut self, P: &[f64], Q: &mut [f64]) {
+        let Config { ambience, .. } = self.config;
+        let System { cores, nodes, ref D, ref Ut, ref El, ref G, ref mut Z, .. } = self.system;
+
+        debug_assert!(P.len() % cores == 0);
+        debug_assert!(Q.len() % cores == 0);
+
+        let steps = P.len() / cores;
+        debug_assert!(steps > 0);
+
+        for (qcolumn, p) in Q.chunks_exact_mut(cores).zip(P.chunks_exact(cores)) {
+            // z[i] = exp(dt·L[i])·z[i] + (G·p)[i]
+            for (i, (z, &el)) in Z.iter_mut().zip(El.iter()).enumerate() {
+                let mut injection = 0.0;
+                for (gcolumn, &pk) in G.chunks_exact(nodes).zip(p.iter()) {
+                    injection += gcolumn[i] * pk;
+                }
+                *z = el * *z + injection;
+            }
+
+            // Project back at the core indices:
+            // Q[j·cores+i] = D[i]·(U·z)[i] + ambience, with Ut column i = U row i.
+            for (q, (&d, utrow)) in qcolumn.iter_mut().zip(D.iter().zip(Ut.chunks_exact(nodes))) {
+                let mut x = 0.0;
+                for (&u, &z) in utrow.iter().zip(Z.iter()) {
+                    x += u * z;
+                }
+                *q = d * x + ambience;
+            }
+        }
+    }
+}
+
+/// Solve `A·X = B` in place for `X` via Gaussian elimination with partial pivoting.
+///
+/// `A` is `order`×`order` and `B` carries `order` right-hand sides, both column-major;
+/// on return `B` holds the solution and `A` its row-reduced form.
+fn solve(A: &mut [f64], B: &mut [f64], order: usize) -> Result<()> {
+    for k in 0..order {
+        let mut pivot = k;
+        let mut maximum = A[k * order + k].abs();
+        for i in (k + 1)..order {
+            let value = A[k * order + i].abs();
+            if value > maximum {
+                maximum = value;
+                pivot = i;
+            }
+        }
+        if maximum == 0.0 {
+            return Err(Error("the matrix is singular".to_string()));
+        }
+        if pivot != k {
+            for j in 0..order {
+                A.swap(j * order + k, j * order + pivot);
+            }
+            for j in 0..order {
+                B.swap(j * order + k, j * order + pivot);
+            }
+        }
+        let diagonal = A[k * order + k];
+        for i in (k + 1)..order {
+            let factor = A[k * order + i] / diagonal;
+            if factor != 0.0 {
+                for j in k..order {
+                    A[j * order + i] -= factor * A[j * order + k];
+                }
+                for j in 0..order {
+                    B[j * order + i] -= factor * B[j * order + k];
+                }
+            }
+        }
+    }
+
+    for j in 0..order {
+        for i in (0..order).rev() {
+            let mut sum = B[j * order + i];
+            for k in (i + 1)..order {
+                sum -= A[k * order + i] * B[j * order + k];
+            }
+            B[j * order + i] = sum / A[i * order + i];
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,94 @@
+use matrix;
+
+use {Analysis, Backend, Circuit, Config, Leakage, Propagation};
+
+const TOLERANCE: f64 = 1e-9;
+
+/// A small symmetric tridiagonal chain of four nodes whose first two are cores.
+fn circuit() -> Circuit {
+    let nodes = 4;
+    let mut values = vec![0.0; nodes * nodes];
+    for i in 0..nodes {
+        values[i * nodes + i] = 2.0;
+        if i + 1 < nodes {
+            values[(i + 1) * nodes + i] = -1.0;
+            values[i * nodes + (i + 1)] = -1.0;
+        }
+    }
+    Circuit {
+        cores: 2,
+        nodes: nodes,
+        capacitance: vec![1.0; nodes],
+        conductance: matrix::Dense { rows: nodes, columns: nodes, values: values },
+    }
+}
+
+fn config(propagation: Propagation, backend: Backend) -> Config {
+    Config {
+        ambience: 318.15,
+        time_step: 1e-2,
+        propagation: propagation,
+        backend: backend,
+        leakage: Leakage::None,
+        tolerance: 1e-12,
+        max_iterations: 20,
+    }
+}
+
+#[test]
+fn modal_matches_dense() {
+    let circuit = circuit();
+    let cores = circuit.cores;
+
+    let mut dense = Analysis::new(&circuit, &config(Propagation::Dense, Backend::Eigen)).unwrap();
+    let mut modal = Analysis::new(&circuit, &config(Propagation::Modal, Backend::Eigen)).unwrap();
+
+    let steps = 8;
+    let P = vec![1.0; steps * cores];
+    let mut Qd = vec![0.0; steps * cores];
+    let mut Qm = vec![0.0; steps * cores];
+
+    dense.step(&P, &mut Qd);
+    modal.step(&P, &mut Qm);
+
+    for (&d, &m) in Qd.iter().zip(Qm.iter()) {
+        assert!((d - m).abs() < TOLERANCE, "dense {} vs modal {}", d, m);
+    }
+}
+
+#[test]
+fn pade_matches_eigen() {
+    let circuit = circuit();
+
+    let eigen = Analysis::new(&circuit, &config(Propagation::Dense, Backend::Eigen)).unwrap();
+    let pade = Analysis::new(&circuit, &config(Propagation::Dense, Backend::Pade)).unwrap();
+
+    for (&a, &b) in eigen.system.E.iter().zip(pade.system.E.iter()) {
+        assert!((a - b).abs() < TOLERANCE, "E: eigen {} vs pade {}", a, b);
+    }
+    for (&a, &b) in eigen.system.F.iter().zip(pade.system.F.iter()) {
+        assert!((a - b).abs() < TOLERANCE, "F: eigen {} vs pade {}", a, b);
+    }
+}
+
+#[test]
+fn steady_state_matches_transient() {
+    let circuit = circuit();
+    let cores = circuit.cores;
+
+    let mut analysis = Analysis::new(&circuit, &config(Propagation::Dense, Backend::Eigen)).unwrap();
+
+    // A long transient under constant power must converge to the direct solution.
+    let steps = 50000;
+    let P = vec![1.0; steps * cores];
+    let mut Q = vec![0.0; steps * cores];
+    analysis.step(&P, &mut Q);
+
+    let mut Qss = vec![0.0; cores];
+    analysis.steady_state(&vec![1.0; cores], &mut Qss).unwrap();
+
+    for (i, &q) in Qss.iter().enumerate() {
+        let transient = Q[(steps - 1) * cores + i];
+        assert!((q - transient).abs() < 1e-6, "core {}: steady {} vs transient {}", i, q, transient);
+    }
+}